@@ -0,0 +1,233 @@
+/*!
+
+Run a job composed of a common *task* (async function) applied to a series of input values,
+concurrently via [join_all][ja] on the single-threaded, current-thread [block_on][bo] executor,
+and collect the results into a `Vec` in input order
+
+Every task future in a job is polled cooperatively on the thread that calls `run_job`/`run_job_async`;
+nothing here spawns onto other OS threads, so a `task` is free to use non-`Send`, thread-local state
+(e.g. `rand::thread_rng()`).
+
+[ja]: https://docs.rs/futures/latest/futures/future/fn.join_all.html
+[bo]: https://docs.rs/futures/latest/futures/executor/fn.block_on.html
+
+This is the library half of `futures-join-all`: a generic `run_job`/`run_job_async` pair that any
+caller can plug their own async `task` and input type into (HTTP fetches, file processing, ...)
+instead of copying the boilerplate. The `futures-join-all` binary is a thin CLI wrapper supplying
+a demo sleep `task` over `u64` inputs, plus some extra bounded/streaming/requeuing modes built
+from the same `futures` primitives.
+
+`run_job`/`run_job_async` assume one homogeneous task producing one output type; for a job whose
+inputs mix several *kinds*, each requiring a different async operation and a different
+intermediate type, see [run_mixed_job]/[run_mixed_job_async] instead. None of these can fail or
+time out; for a `task` that might hang, see [run_job_with_timeout]/[run_job_with_timeout_async].
+
+*/
+
+use async_std::task::sleep;
+use futures::executor::block_on;
+use futures::future::{join_all, select, Either};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/**
+
+Run `task` over every item in `inputs` via [join_all][ja] on the current thread, blocking it until
+every future resolves, and return the results in input order
+
+This is the one-liner convenience wrapped around [run_job_async]; prefer that instead if you're
+already inside a runtime, since calling `run_job` from one would double-block.
+
+[ja]: https://docs.rs/futures/latest/futures/future/fn.join_all.html
+
+*/
+pub fn run_job<I, T, F, Fut, O>(inputs: I, task: F) -> Vec<O>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = O>,
+{
+    block_on(run_job_async(inputs, task))
+}
+
+/**
+
+Async counterpart of [run_job] for callers already inside a runtime
+
+*/
+pub async fn run_job_async<I, T, F, Fut, O>(inputs: I, task: F) -> Vec<O>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = O>,
+{
+    join_all(inputs.into_iter().map(task)).await
+}
+
+/// One boxed, pinned task future in a [run_mixed_job] batch; build one per input with
+/// `.boxed_local()` from [futures::future::FutureExt], whatever its own concrete future type is
+///
+/// Not `Send`: tasks run cooperatively on the single thread that calls `run_mixed_job`/
+/// `run_mixed_job_async`, so a task is free to use thread-local state (e.g. `rand::thread_rng()`)
+/// across an `.await`.
+pub type MixedFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+/**
+
+Run a batch of heterogeneous `tasks`, each already boxed into a [MixedFuture] by the caller (e.g.
+via `.boxed_local()`), blocking the calling thread until every one of them resolves, and return
+the `Acc` that they all folded their own results into
+
+Unlike [run_job], this doesn't return a `Vec` of one output type: every input `kind` in the batch
+can drive a different async operation and intermediate type, so instead each task locks `acc` and
+writes its own result into it when ready. `acc` must be the sole remaining `Arc` once every task
+has completed (i.e. the tasks hold the only other clones, and drop them on completion), or this
+panics. Reach for [run_job] instead when every input maps to the same task and output type; the
+boxing here has real overhead not every caller wants.
+
+*/
+pub fn run_mixed_job<Acc>(tasks: Vec<MixedFuture>, acc: Arc<Mutex<Acc>>) -> Acc {
+    block_on(run_mixed_job_async(tasks, acc))
+}
+
+/**
+
+Async counterpart of [run_mixed_job] for callers already inside a runtime
+
+*/
+pub async fn run_mixed_job_async<Acc>(tasks: Vec<MixedFuture>, acc: Arc<Mutex<Acc>>) -> Acc {
+    join_all(tasks).await;
+    Arc::try_unwrap(acc)
+        .unwrap_or_else(|_| panic!("run_mixed_job_async: tasks left outstanding clones of `acc`"))
+        .into_inner()
+        .expect("run_mixed_job_async: accumulator mutex poisoned")
+}
+
+/// Ways a per-task future can fail in [run_job_with_timeout]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobError {
+    /// The task did not resolve within the configured deadline
+    Timeout,
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobError::Timeout => write!(f, "task timed out"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
+
+/**
+
+Run `task` over every item in `inputs` like [run_job], but race each one against a `timeout`
+deadline via [select][s] so a single hung task yields `Err(JobError::Timeout)` instead of
+stalling the rest of the job forever
+
+[s]: https://docs.rs/futures/latest/futures/future/fn.select.html
+
+*/
+pub fn run_job_with_timeout<I, T, F, Fut, O>(inputs: I, task: F, timeout: Duration) -> Vec<Result<O, JobError>>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = O>,
+{
+    block_on(run_job_with_timeout_async(inputs, task, timeout))
+}
+
+/**
+
+Async counterpart of [run_job_with_timeout] for callers already inside a runtime
+
+*/
+pub async fn run_job_with_timeout_async<I, T, F, Fut, O>(
+    inputs: I,
+    task: F,
+    timeout: Duration,
+) -> Vec<Result<O, JobError>>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = O>,
+{
+    let raced = inputs.into_iter().map(|t| {
+        let task_fut = task(t);
+        async move {
+            match select(Box::pin(task_fut), Box::pin(sleep(timeout))).await {
+                Either::Left((result, _)) => Ok(result),
+                Either::Right((_, _)) => Err(JobError::Timeout),
+            }
+        }
+    });
+    join_all(raced).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::future::FutureExt;
+
+    #[test]
+    fn run_job_preserves_input_order() {
+        let results = run_job(1..=5, |n| async move { n * n });
+        assert_eq!(results, vec![1, 4, 9, 16, 25]);
+    }
+
+    #[test]
+    fn run_job_async_preserves_input_order() {
+        let results = block_on(run_job_async(1..=5, |n| async move { n * n }));
+        assert_eq!(results, vec![1, 4, 9, 16, 25]);
+    }
+
+    #[test]
+    fn run_mixed_job_folds_every_task_into_the_accumulator() {
+        let acc = Arc::new(Mutex::new(vec![]));
+        let tasks: Vec<MixedFuture> = (1..=3)
+            .map(|n| {
+                let acc = acc.clone();
+                async move { acc.lock().unwrap().push(n * n) }.boxed_local()
+            })
+            .collect();
+        let mut results = run_mixed_job(tasks, acc);
+        results.sort_unstable();
+        assert_eq!(results, vec![1, 4, 9]);
+    }
+
+    #[test]
+    #[should_panic(expected = "outstanding clones")]
+    fn run_mixed_job_panics_if_a_task_leaks_an_acc_clone() {
+        let acc = Arc::new(Mutex::new(0));
+        let leaked = acc.clone();
+        let tasks: Vec<MixedFuture> = vec![async move {
+            std::mem::forget(leaked);
+        }
+        .boxed_local()];
+        run_mixed_job(tasks, acc);
+    }
+
+    #[test]
+    fn run_job_with_timeout_async_returns_ok_when_within_the_deadline() {
+        let results = block_on(run_job_with_timeout_async(
+            1..=3,
+            |n| async move { n * n },
+            Duration::from_secs(5),
+        ));
+        assert_eq!(results, vec![Ok(1), Ok(4), Ok(9)]);
+    }
+
+    #[test]
+    fn run_job_with_timeout_async_returns_err_when_the_deadline_is_exceeded() {
+        let results = block_on(run_job_with_timeout_async(
+            1..=1,
+            |_| sleep(Duration::from_secs(5)),
+            Duration::from_millis(1),
+        ));
+        assert_eq!(results, vec![Err(JobError::Timeout)]);
+    }
+}
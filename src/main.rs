@@ -1,11 +1,8 @@
 /*!
 
-Define a job composed of a common *task* (async function) to be run on a series of input values,
-run it concurrently via [join_all][ja] on an implicit [ThreadPool][tp] (1 thread per logical CPU
-core), and implicitly "collect" the ordered results into a vector
-
-[ja]: https://docs.rs/futures/latest/futures/future/fn.join_all.html
-[tp]: https://docs.rs/futures/latest/futures/executor/struct.ThreadPool.html
+CLI wrapper around the [futures_join_all] library: supplies a demo sleep `task` over `u64`
+inputs and a handful of extra concurrency modes (bounded, streaming, requeuing) built from the
+same `futures` primitives as [futures_join_all::run_job].
 
 # Example
 
@@ -42,10 +39,91 @@ sleeping concurrently.
 Each task prints its `slept` message when it is done and returns.
 The results are collected and printed.
 
+# Bounded concurrency
+
+By default every task is fired at once, which is fine for a quick demo but doesn't scale to large
+`N` (thousands of open sleeps/sockets). Pass `-j`/`--concurrency LIMIT` to instead drive the job
+through [buffer_unordered][bu], which keeps at most `LIMIT` futures polling at a time and pulls a
+new one off the iterator each time one finishes:
+
+```text
+$ ./target/release/futures-join-all -j 4 10
+```
+
+`buffer_unordered` yields results in completion order rather than input order, so each output is
+paired with its input index and the results are re-sorted before printing to preserve the same
+`results = [...]` shape as the unbounded mode.
+
+[bu]: https://docs.rs/futures/latest/futures/stream/trait.StreamExt.html#method.buffer_unordered
+
+# Streaming results as they complete
+
+`join_all` (and `buffer_unordered` as collected above) only return once *every* task has
+finished, so nothing is printed until the slowest one completes. Pass `--as-completed` to instead
+drive the job with [FuturesUnordered][fu] and print each result the instant it resolves, then
+still collect the same ordered `Vec` at the end for anything that needs it:
+
+```text
+$ ./target/release/futures-join-all --as-completed 10
+```
+
+[fu]: https://docs.rs/futures/latest/futures/stream/struct.FuturesUnordered.html
+
+# Requeuing work while the job runs
+
+Sometimes a result isn't really "done" yet: a unit failed and should be retried, or it uncovered
+more work to do. Pass `--retry-short` to run a demo of this: it treats any sleep under 3 seconds
+as a failure worth retrying, feeding the same input back into the `FuturesUnordered` set via an
+`on_complete` callback rather than stopping once the first pass over `1..=num` finishes.
+
+```text
+$ ./target/release/futures-join-all --retry-short 10
+```
+
+# Progress reporting
+
+Plain `println!` from each task interleaves badly with a rendered progress bar. Pass `--progress`
+to show an overall bar sized to `N`, ticked once per completed task, with every `sleeping`/`slept`
+line routed through [indicatif][id]'s "print above the bar" mechanism so the two never clobber
+each other:
+
+```text
+$ ./target/release/futures-join-all --progress 10
+```
+
+[id]: https://docs.rs/indicatif
+
+# Mixing task kinds
+
+Pass `--mixed-demo` to run [futures_join_all::run_mixed_job] instead: half the inputs sleep like
+`task` above, the other half just compute a square, and both kinds fold their own line into one
+shared `Vec<String>` log instead of a homogeneous `Vec` of one output type:
+
+```text
+$ ./target/release/futures-join-all --mixed-demo 10
+```
+
+# Per-task timeouts
+
+The demo `task` can never fail, but a real one (network I/O, say) can hang. Pass `--timeout SECS`
+to race each task against that deadline via [futures_join_all::run_job_with_timeout], so a task
+that runs long yields `Err(JobError::Timeout)` instead of stalling the whole job:
+
+```text
+$ ./target/release/futures-join-all --timeout 5 10
+```
+
 */
 
-use futures::future::join_all;
+mod progress;
+
 use futures::executor::block_on;
+use futures::future::FutureExt;
+use futures::stream::{self, FuturesUnordered, StreamExt};
+use futures_join_all::{run_job, run_job_with_timeout, run_mixed_job, MixedFuture};
+use progress::Progress;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 // Only used for `sleep()`:
 use rand::Rng;
@@ -83,6 +161,155 @@ async fn task(n: u64) -> (u64, u64) {
 
 /**
 
+Same as `task`, but route its `sleeping`/`slept` lines through `progress` instead of `println!` so
+they print above the bar instead of clobbering it
+
+*/
+async fn task_with_progress(n: u64, progress: Progress) -> (u64, u64) {
+
+    // Sleep for a random number of seconds
+    let mut rng = rand::thread_rng();
+    let secs: u64 = rng.gen_range(1, 11); // 1-10
+    progress.println(&format!("task {} sleeping {}", n, secs));
+    sleep(Duration::from_secs(secs)).await;
+    progress.println(&format!("task {} slept {}", n, secs));
+
+    // Return result
+    (n, secs)
+}
+
+/**
+
+Run `task_with_progress` over `1..=num` via [FuturesUnordered][fu], ticking `progress` once per
+completed task and finishing the bar once the job drains
+
+[fu]: https://docs.rs/futures/latest/futures/stream/struct.FuturesUnordered.html
+
+*/
+async fn run_with_progress(num: u64, progress: Progress) -> Vec<(u64, u64)> {
+    let mut set: FuturesUnordered<_> = (1..=num).map(|n| task_with_progress(n, progress.clone())).collect();
+    let mut results = vec![];
+    while let Some(result) = set.next().await {
+        progress.inc();
+        results.push(result);
+    }
+    progress.finish();
+    results.sort_by_key(|(n, _)| *n);
+    results
+}
+
+/**
+
+Run `task` over `1..=num`, capping how many tasks run concurrently at `limit` via
+[buffer_unordered][bu], then restore input order before returning
+
+[bu]: https://docs.rs/futures/latest/futures/stream/trait.StreamExt.html#method.buffer_unordered
+
+*/
+async fn run_bounded(num: u64, limit: usize) -> Vec<(u64, u64)> {
+    let mut results: Vec<(u64, (u64, u64))> = stream::iter((1..=num).map(|x| async move { (x, task(x).await) }))
+        .buffer_unordered(limit)
+        .collect::<Vec<_>>()
+        .await;
+    results.sort_by_key(|(x, _)| *x);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/**
+
+Run `task` over `1..=num` via [FuturesUnordered][fu], printing each result the instant it
+completes, and also collecting the ordered `Vec` for callers that need it
+
+[fu]: https://docs.rs/futures/latest/futures/stream/struct.FuturesUnordered.html
+
+*/
+async fn run_as_completed(num: u64) -> Vec<(u64, u64)> {
+    let mut set: FuturesUnordered<_> = (1..=num).map(task).collect();
+    let mut results = vec![];
+    while let Some(result) = set.next().await {
+        println!("completed: {:?}", result);
+        results.push(result);
+    }
+    results.sort_by_key(|(x, _)| *x);
+    results
+}
+
+/**
+
+Drive a [FuturesUnordered][fu] over `1..=num`, calling `on_complete` with each result as it
+resolves and pushing a `task` future for every input it returns back into the same set
+
+The loop only ends once the set has drained with no new work queued, which turns the otherwise
+fixed `1..=num` job into a work-list engine while keeping the single-executor concurrency model.
+A retried input overwrites its earlier (failed) result rather than appending to it, so the
+returned `Vec` holds one final, ordered result per input, like every other mode.
+
+[fu]: https://docs.rs/futures/latest/futures/stream/struct.FuturesUnordered.html
+
+*/
+async fn run_with_requeue<F>(num: u64, on_complete: F) -> Vec<(u64, u64)>
+where
+    F: Fn(&(u64, u64)) -> Vec<u64>,
+{
+    let mut set: FuturesUnordered<_> = (1..=num).map(task).collect();
+    let mut results = HashMap::new();
+    while let Some(result) = set.next().await {
+        for requeued in on_complete(&result) {
+            set.push(task(requeued));
+        }
+        results.insert(result.0, result);
+    }
+    let mut results: Vec<(u64, u64)> = results.into_values().collect();
+    results.sort_by_key(|(n, _)| *n);
+    results
+}
+
+/**
+
+Odd-numbered kind of `--mixed-demo` input: sleep like `task`, then push a line describing the
+sleep into the shared `acc` log
+
+*/
+async fn mixed_sleep_kind(n: u64, acc: Arc<Mutex<Vec<String>>>) {
+    let (n, secs) = task(n).await;
+    acc.lock().unwrap().push(format!("sleep({}) -> slept {}", n, secs));
+}
+
+/**
+
+Even-numbered kind of `--mixed-demo` input: compute a square with no sleep at all, then push a
+line describing it into the same shared `acc` log
+
+*/
+async fn mixed_square_kind(n: u64, acc: Arc<Mutex<Vec<String>>>) {
+    let result = n * n;
+    acc.lock().unwrap().push(format!("square({}) -> {}", n, result));
+}
+
+/**
+
+Build a batch mixing `mixed_sleep_kind` and `mixed_square_kind` over `1..=num`, boxing each into a
+[MixedFuture] so the two different task/output types can run through a single
+[futures_join_all::run_mixed_job] call and fold into one shared `Vec<String>` log
+
+*/
+fn run_mixed_demo(num: u64) -> Vec<String> {
+    let acc = Arc::new(Mutex::new(vec![]));
+    let tasks: Vec<MixedFuture> = (1..=num)
+        .map(|n| {
+            let acc = acc.clone();
+            if n % 2 == 1 {
+                mixed_sleep_kind(n, acc).boxed_local()
+            } else {
+                mixed_square_kind(n, acc).boxed_local()
+            }
+        })
+        .collect();
+    run_mixed_job(tasks, acc)
+}
+
+/**
+
 Command line interface
 
 */
@@ -95,19 +322,70 @@ Usage: `futures-join-all [OPTIONS] N`
 
 * `N`: Number of tasks
 * `OPTIONS`
+    * `-j`, `--concurrency LIMIT`: Cap the number of tasks running at once (default: unbounded)
+    * `--as-completed`: Print each result as soon as it completes, instead of waiting on all of them
+    * `--retry-short`: Demo requeuing work: retry any task that slept under 3 seconds
+    * `--progress`: Show an overall progress bar instead of interleaving task output with it
+    * `--mixed-demo`: Demo heterogeneous task kinds folding into a shared accumulator
+    * `-t`, `--timeout SECS`: Fail any task exceeding this deadline instead of waiting on it forever
     * `-h`, `--help`: Print usage
 ");
         exit(0);
     }
+    let mut concurrency: Option<usize> = None;
+    let mut as_completed = false;
+    let mut retry_short = false;
+    let mut progress = false;
+    let mut mixed_demo = false;
+    let mut timeout: Option<u64> = None;
     let mut a = vec![];
-    for arg in args().skip(1) {
+    let mut args = args().skip(1);
+    while let Some(arg) = args.next() {
         if ["-h", "--help"].contains(&arg.as_str()) {
             usage();
+        } else if arg == "--as-completed" {
+            as_completed = true;
+        } else if arg == "--retry-short" {
+            retry_short = true;
+        } else if arg == "--progress" {
+            progress = true;
+        } else if arg == "--mixed-demo" {
+            mixed_demo = true;
+        } else if ["-t", "--timeout"].contains(&arg.as_str()) {
+            let secs = match args.next() {
+                Some(x) => match x.parse::<u64>() {
+                    Ok(u) if u > 0 => u,
+                    _ => {
+                        eprintln!("ERROR: Failed to parse a positive integer timeout in seconds from `{}`!", x);
+                        exit(1);
+                    },
+                },
+                None => {
+                    eprintln!("ERROR: `{}` requires a SECS argument!", arg);
+                    exit(1);
+                },
+            };
+            timeout = Some(secs);
+        } else if ["-j", "--concurrency"].contains(&arg.as_str()) {
+            let limit = match args.next() {
+                Some(x) => match x.parse::<usize>() {
+                    Ok(u) if u > 0 => u,
+                    _ => {
+                        eprintln!("ERROR: Failed to parse a positive integer concurrency limit from `{}`!", x);
+                        exit(1);
+                    },
+                },
+                None => {
+                    eprintln!("ERROR: `{}` requires a LIMIT argument!", arg);
+                    exit(1);
+                },
+            };
+            concurrency = Some(limit);
         } else {
             a.push(arg);
         }
     }
-    if a.len() < 1 {
+    if a.is_empty() {
         usage();
     }
     let nums: Vec<u64> = a.iter().map(|x| {
@@ -123,22 +401,7 @@ Usage: `futures-join-all [OPTIONS] N`
     for num in nums {
 
         /*
-        // Option 1: Define the whole job as a self-contained unit
-        let job = async { join_all((1..=num).map(|x| task(x))).await };
-        let results = block_on(job);
-        */
-
-        /*
-        // Option 2: Define inputs separately and use the boilerplate job
-        let inputs = 1..=num;
-        let results = block_on(async { join_all(inputs.map(|x| task(x))).await });
-        */
-
-        // Option 3: Basically options 1 and 2 combined as a "one-liner"
-        let results = block_on(async { join_all((1..=num).map(|x| task(x))).await });
-
-        /*
-        // Option 4: Use an async closure (currently unstable)
+        // Use an async closure (currently unstable)
         // (https://github.com/rust-lang/rust/issues/62290) instead of an async function
         let results = block_on(async { join_all((1..=num).map(|x| (async |n| {
             println!("sleeping {}", n);
@@ -148,6 +411,45 @@ Usage: `futures-join-all [OPTIONS] N`
         }).())).await });
         */
 
+        // `--mixed-demo` folds two different task/output kinds into a shared accumulator instead
+        // of a single homogeneous `Vec`; report it separately and move on to the next `num`
+        if mixed_demo {
+            let results = run_mixed_demo(num);
+            println!("\nresults = {:?}\n", results);
+            continue;
+        }
+
+        // A `--timeout` races each task against a deadline and so returns a `Result` per task
+        // instead of a bare output; report it separately and move on to the next `num`
+        if let Some(secs) = timeout {
+            let results = run_job_with_timeout(1..=num, task, Duration::from_secs(secs));
+            println!("\nresults = {:?}\n", results);
+            continue;
+        }
+
+        // Bounded concurrency via `buffer_unordered`, streaming via `FuturesUnordered`, requeuing
+        // retries via `FuturesUnordered`, progress reporting, or the library's unbounded
+        // `run_job`, depending on which flags were given
+        let results = if progress {
+            block_on(run_with_progress(num, Progress::new(num)))
+        } else if retry_short {
+            block_on(run_with_requeue(num, |&(n, secs)| {
+                if secs < 3 {
+                    println!("task {} slept only {}, retrying", n, secs);
+                    vec![n]
+                } else {
+                    vec![]
+                }
+            }))
+        } else if as_completed {
+            block_on(run_as_completed(num))
+        } else {
+            match concurrency {
+                Some(limit) => block_on(run_bounded(num, limit)),
+                None => run_job(1..=num, task),
+            }
+        };
+
         // Print the results
         println!("\nresults = {:?}\n", results);
 
@@ -0,0 +1,53 @@
+/*!
+
+Thin wrapper around [indicatif] so concurrently running task futures can tick a shared progress
+bar without their own `println!` output clobbering it
+
+[indicatif]: https://docs.rs/indicatif
+
+*/
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+/**
+
+Cheaply cloneable handle around one overall progress bar, sized to the number of tasks in the job
+
+`ProgressBar` is already an `Arc` internally, so cloning this handle is cheap and safe to hand to
+every task future in the job, even though they all run cooperatively on the single thread that
+calls `block_on` rather than in parallel.
+
+*/
+#[derive(Clone)]
+pub struct Progress {
+    bar: ProgressBar,
+}
+
+impl Progress {
+
+    /// Build a bar sized to `total` tasks
+    pub fn new(total: u64) -> Self {
+        let bar = ProgressBar::new(total);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{bar:40.cyan/blue} {pos}/{len} ({eta})")
+                .expect("valid progress bar template"),
+        );
+        Progress { bar }
+    }
+
+    /// Print `message` above the bar instead of interleaving with it
+    pub fn println(&self, message: &str) {
+        self.bar.println(message);
+    }
+
+    /// Tick the bar by one, call once a task resolves
+    pub fn inc(&self) {
+        self.bar.inc(1);
+    }
+
+    /// Finish and clear the bar once the whole job completes
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+}